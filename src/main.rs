@@ -50,7 +50,11 @@ fn main() {
     // show(&table);
 
     // References are non-owning pointers, so the table variable remains the owner of the entire structure, show has just borrowed it for a bit. We'll also need to adjust the definition of show to match:
-    fn show(table: &Table) {
+
+    // Nothing about the borrowing here actually depends on the key being a String or the value being a Vec<String>, show only needs to be able to print them. So instead of pinning show to Table, we give it a key type K and a value type V, each bounded by Display, and let it work over any HashMap<K, Vec<V>>. Table still satisfies those bounds, so every existing call site is untouched.
+    use std::fmt::Display;
+
+    fn show<K: Display, V: Display>(table: &HashMap<K, Vec<V>>) {
         for (artist, works) in table {
             println!("works by {}:", artist);
 
@@ -60,12 +64,20 @@ fn main() {
         }
     }
 
+    // To prove show really does drive any HashMap<K, Vec<V>> and not just Table, here it is called on a composer-to-opus-numbers map instead of an artist-to-works one:
+    let mut opus_numbers: HashMap<String, Vec<u32>> = HashMap::new();
+    opus_numbers.insert("Beethoven".to_string(), vec![27, 55, 67]);
+    opus_numbers.insert("Brahms".to_string(), vec![68, 98]);
+    show(&opus_numbers);
+
     // The type of show's parameter table has changed from Table to &Table. Instead of passing the table bu value (and hence moving ownership into the function), we're now passing a shared reference. How does this work within the body?
     // The original outer loop took ownership of the HashMap and consumed it, in our new version it receives a shared reference to the HashMap. Iterating over a shared reference (of HashMap) is defined to produce shared references to each entry's key and value. Artist has changed from a String to a &String, and works from a Vec<String> to a &Vec<String>.
     // The inner loop is changed similarly. Iterating over a shared ref to a vector is defined to produce shared refs to its elements. So work is now a &String. No ownership changes hands anywhere in the function, just a passing of non-owning references.
 
     // If we wanted to write a function to alphabetize the works of each artist, a shared reference doesn't suffice. Shared references don't permit modification. Instead, the sorting fucntion needs to take a mutable reference to the table.
-    fn sort_works(table: &mut Table) {
+
+    // sort_works only ever calls sort on each Vec<V>, so it generalizes the same way: any key type K (sort_works never looks at it, so there's no bound) and any value type V that's Ord, since sort requires its elements to be comparable.
+    fn sort_works<K, V: Ord>(table: &mut HashMap<K, Vec<V>>) {
         for (_artist, works) in table {
             works.sort();
         }
@@ -75,6 +87,146 @@ fn main() {
     sort_works(&mut table);
 
     // This mutable borrow grants sort_works the ablility to read and modify our structure as required by the vectors' sort method.
-    
+
     // When we pass a value to a function in a way that moves ownership of the value to the function, we say that we have passed it 'by value'. If we instead pass the function a reference to the value, we say that we have passed the value 'by reference'. This is what we did above with our show function. Many languages make the difference with value vs reference, however if Rust it's very important as it pertains to how ownership is affected.
+
+    // show only ever reads the table and sort_works only reorders it, neither of them asks questions of it. Once we're comfortable passing a shared reference into a function that just reads, it's natural to build a small family of query functions on top of the same borrow. Since a shared reference is Copy, the table's owner is free to keep using table after any number of these calls.
+
+    // works_matching borrows the table and, for every artist, filters that artist's works down to the ones containing a keyword, pairing each surviving work back up with its artist. flat_map lets us turn the one works-list per artist into a flattened stream of (artist, work) pairs.
+    fn works_matching<'t>(table: &'t Table, keyword: &str) -> Vec<(&'t String, &'t String)> {
+        table
+            .iter()
+            .flat_map(|(artist, works)| {
+                works
+                    .iter()
+                    .filter(move |w| w.contains(keyword))
+                    .map(move |w| (artist, w))
+            })
+            .collect()
+    }
+
+    // total_works adds up the lengths of every works Vec without ever taking ownership of them.
+    fn total_works(table: &Table) -> usize {
+        table.values().map(Vec::len).sum()
+    }
+
+    // busiest_artist finds the artist with the most works. max_by_key hands back a shared reference to the winning entry, which we then narrow down to just the artist's name.
+    fn busiest_artist(table: &Table) -> Option<&String> {
+        table.iter().max_by_key(|(_, works)| works.len()).map(|(a, _)| a)
+    }
+
+    let matches = works_matching(&table, "a");
+    println!("works containing 'a': {}", matches.len());
+
+    println!("total works tracked: {}", total_works(&table));
+
+    if let Some(artist) = busiest_artist(&table) {
+        println!("busiest artist: {}", artist);
+    }
+
+    // table is still ours after all three calls, none of them asked to own it.
+    assert_eq!(table["Gesualdo"][0], "Tenebrae Responsoria");
+
+    // It's common to build up a Table in pieces, say one per source, and then combine them. merge puts the shared-vs-mutable contrast to work on a real aggregation: from is only ever read through a shared reference, so its owner can keep using it after the call, while into is read and written through an exclusive &mut reference.
+    fn merge(into: &mut Table, from: &Table) {
+        for (artist, works) in from {
+            let entry = into.entry(artist.clone()).or_default();
+            entry.extend(works.iter().cloned());
+            entry.sort();
+            entry.dedup();
+        }
+    }
+
+    let mut more_works = Table::new();
+    more_works.insert("Gesualdo".to_string(), vec!["Tenebrae Responsoria".to_string(), "Moro, lasso".to_string()]);
+    more_works.insert("Artemisia Gentileschi".to_string(), vec!["Judith Slaying Holofernes".to_string()]);
+
+    merge(&mut table, &more_works);
+
+    // from is untouched, it was only ever borrowed, so we can still read from it here.
+    assert_eq!(more_works["Gesualdo"].len(), 2);
+    assert_eq!(table["Gesualdo"].len(), 3);
+
+    // & and &mut enforce multiple readers or single writer at compile time, for a single thread. If we want to share a Table across threads, we need a runtime version of the same rule. Arc lets several threads jointly own the table, and RwLock lets any number of them read at once but only one of them write, exactly mirroring shared vs mutable references.
+    use std::sync::{Arc, RwLock};
+
+    struct SharedTable {
+        table: Arc<RwLock<Table>>,
+    }
+
+    impl SharedTable {
+        fn new(table: Table) -> SharedTable {
+            SharedTable { table: Arc::new(RwLock::new(table)) }
+        }
+
+        // read_with takes out a read lock, hands the closure a shared reference to the table, and releases the lock once the closure returns. Any number of threads can be inside read_with at the same time.
+        fn read_with<F, R>(&self, f: F) -> R
+        where
+            F: FnOnce(&Table) -> R,
+        {
+            let guard = self.table.read().unwrap();
+            f(&guard)
+        }
+
+        // write_with takes out the write lock instead, so the closure gets a mutable reference. While a thread is inside write_with, every other reader and writer is blocked.
+        fn write_with<F, R>(&self, f: F) -> R
+        where
+            F: FnOnce(&mut Table) -> R,
+        {
+            let mut guard = self.table.write().unwrap();
+            f(&mut guard)
+        }
+    }
+
+    let shared = SharedTable::new(table);
+
+    let artist_count = shared.read_with(|table| table.len());
+    println!("shared table has {} artists", artist_count);
+
+    shared.write_with(sort_works);
+
+    // Hand-writing "X".to_string() and vec![...] at every call site works, but it's tedious and it's easy to forget the .to_string() on one of the entries. add_artist takes borrowed &str arguments and does the String conversions itself, only allocating owned data at the point it's actually inserted, and it merges into an existing artist's works rather than clobbering them.
+    fn add_artist(table: &mut Table, name: &str, works: &[&str]) {
+        let entry = table.entry(name.to_string()).or_default();
+        entry.extend(works.iter().map(|w| w.to_string()));
+    }
+
+    let mut more_gesualdo = Table::new();
+    add_artist(&mut more_gesualdo, "Gesualdo", &["Moro, lasso"]);
+    add_artist(&mut more_gesualdo, "Gesualdo", &["O voi, troppo felici"]);
+    assert_eq!(more_gesualdo["Gesualdo"].len(), 2);
+
+    // TableBuilder wraps the same idea in a fluent API, so a whole table can be assembled in one expression. It still only ever takes &str slices from the caller, the owned Strings are allocated inside each .artist() call as the works are inserted, and .build() just moves the accumulated Table out.
+    struct TableBuilder {
+        table: Table,
+    }
+
+    impl TableBuilder {
+        fn new() -> TableBuilder {
+            TableBuilder { table: Table::new() }
+        }
+
+        fn artist(mut self, name: &str, works: &[&str]) -> TableBuilder {
+            add_artist(&mut self.table, name, works);
+            self
+        }
+
+        fn build(self) -> Table {
+            self.table
+        }
+    }
+
+    let built = TableBuilder::new()
+        .artist("Bernini", &["Apollo and Daphne", "The Ecstasy of Saint Teresa"])
+        .artist("Bernini", &["David"])
+        .build();
+    assert_eq!(built["Bernini"].len(), 3);
+
+    // Cloning the Arc, not the Table, is what lets worker threads share ownership.
+    let shared_for_worker = shared.table.clone();
+    let handle = std::thread::spawn(move || {
+        let table = shared_for_worker.read().unwrap();
+        total_works(&table)
+    });
+    println!("background total: {}", handle.join().unwrap());
 }